@@ -0,0 +1,414 @@
+use std::fmt;
+use std::net::SocketAddr;
+
+use crate::grammar;
+use crate::ip::{Ip, Network};
+use crate::ParseError;
+
+/// Lookup order as set by the `lookup` directive (a glibc extension)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lookup {
+    /// look up names in `/etc/hosts`
+    File,
+    /// look up names using DNS
+    Bind,
+    /// an unrecognized lookup source, kept verbatim
+    Extra(String),
+}
+
+/// Address family preference as set by the `family` directive (a glibc extension)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    /// prefer IPv4 addresses
+    Inet4,
+    /// prefer IPv6 addresses
+    Inet6,
+}
+
+/// Represents a `resolv.conf` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// List of nameservers
+    pub nameservers: Vec<Ip>,
+    /// Domain to append to the name when it doesn't contain dots
+    pub domain: Option<String>,
+    /// List of suffixes to append to name when it doesn't contain dots
+    pub search: Vec<String>,
+    /// List of preferred addresses
+    pub sortlist: Vec<Network>,
+    /// Enable DNS resolver debugging
+    pub debug: bool,
+    /// The number of dots in a name that triggers an absolute lookup first (default 1)
+    pub ndots: u32,
+    /// Timeout to wait for a response, in seconds (default 5)
+    pub timeout: u32,
+    /// Number of attempts to resolve a name before giving up (default 2)
+    pub attempts: u32,
+    /// Round-robin selection of nameservers
+    pub rotate: bool,
+    /// Don't check names for validity
+    pub no_check_names: bool,
+    /// Try the AAAA query before the A query
+    pub inet6: bool,
+    /// Use the bit-string format for IPv6 reverse lookups
+    pub ip6_bytestring: bool,
+    /// Use the nibble format for IPv6 reverse lookups
+    pub ip6_dotint: bool,
+    /// Enable support for the EDNS0 extension
+    pub edns0: bool,
+    /// Perform IPv4 and IPv6 queries sequentially instead of in parallel
+    pub single_request: bool,
+    /// Reuse a single socket for the A and AAAA queries
+    pub single_request_reopen: bool,
+    /// Don't check `/etc/resolv.conf` for changes while the process is running
+    pub no_reload: bool,
+    /// Request the AD bit in queries and expect it in responses
+    pub trust_ad: bool,
+    /// Don't send queries for top-level-domain-only names
+    pub no_tld_query: bool,
+    /// Always use TCP for DNS queries
+    pub use_vc: bool,
+    /// Lookup order, as set by the `lookup` directive
+    pub lookup: Vec<Lookup>,
+    /// Address family preference, as set by the `family` directive
+    pub family: Vec<Family>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            nameservers: Vec::new(),
+            domain: None,
+            search: Vec::new(),
+            sortlist: Vec::new(),
+            debug: false,
+            ndots: 1,
+            timeout: 5,
+            attempts: 2,
+            rotate: false,
+            no_check_names: false,
+            inet6: false,
+            ip6_bytestring: false,
+            ip6_dotint: false,
+            edns0: false,
+            single_request: false,
+            single_request_reopen: false,
+            no_reload: false,
+            trust_ad: false,
+            no_tld_query: false,
+            use_vc: false,
+            lookup: Vec::new(),
+            family: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Create a new config with default options and no nameservers
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Parse a buffer into a `Config`
+    pub fn parse<T: AsRef<[u8]>>(buf: T) -> Result<Config, ParseError> {
+        grammar::parse(buf.as_ref())
+    }
+
+    /// Like `Config::parse`, but also rejects `domain`/`search` entries that
+    /// aren't valid RFC 1035 hostnames (returning `ParseError::InvalidHostname`).
+    /// Use this when the config will be consumed by a resolver that trusts
+    /// the search list without re-validating it itself.
+    pub fn parse_strict<T: AsRef<[u8]>>(buf: T) -> Result<Config, ParseError> {
+        grammar::parse_strict(buf.as_ref())
+    }
+
+    /// Overlay the `LOCALDOMAIN` and `RES_OPTIONS` environment variables on
+    /// top of this config, the way glibc's stub resolver lets the
+    /// environment override `/etc/resolv.conf`: `LOCALDOMAIN` replaces the
+    /// `search` list, and `RES_OPTIONS` tokens (e.g. `ndots:3 rotate`) are
+    /// applied the same way an `options` line would be, overriding the
+    /// file's settings token by token.
+    pub fn apply_environment(&mut self) -> Result<(), ParseError> {
+        self.apply_options(
+            std::env::var("LOCALDOMAIN").ok(),
+            std::env::var("RES_OPTIONS").ok(),
+        )
+    }
+
+    /// Like `apply_environment`, but takes the variable values directly
+    /// instead of reading the process environment.
+    pub fn apply_options(
+        &mut self,
+        localdomain: Option<String>,
+        res_options: Option<String>,
+    ) -> Result<(), ParseError> {
+        if let Some(localdomain) = localdomain {
+            self.set_search(localdomain.split_whitespace().map(str::to_string).collect());
+        }
+        if let Some(res_options) = res_options {
+            for token in res_options.split_whitespace() {
+                grammar::apply_option_token(self, token, 0).map_err(|_| {
+                    ParseError::InvalidEnvironmentOption { var: "RES_OPTIONS" }
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a buffer into a `Config`, recording every recoverable
+    /// `ParseError` instead of stopping at the first one.
+    ///
+    /// Every line that could be understood is applied to the returned
+    /// `Config`, even if other lines are malformed; the accumulated errors
+    /// describe everything that was skipped. `ParseError::InvalidUtf8` is
+    /// still fatal and stops the parse, since there's nothing useful to
+    /// resynchronize on afterwards.
+    pub fn parse_lenient<T: AsRef<[u8]>>(buf: T) -> (Config, Vec<ParseError>) {
+        grammar::parse_lenient(buf.as_ref())
+    }
+
+    /// The `nameservers` as `SocketAddr`s, using `default_port` (typically
+    /// `53`) for any nameserver whose directive didn't specify a port.
+    pub fn nameservers_as_socket_addrs(&self, default_port: u16) -> Vec<SocketAddr> {
+        self.nameservers
+            .iter()
+            .map(|ip| ip.to_socket_addr(default_port))
+            .collect()
+    }
+
+    /// Set the `domain` directive, overwriting any value set previously
+    pub fn set_domain(&mut self, domain: String) {
+        self.domain = Some(domain);
+    }
+
+    /// Set the `search` directive, overwriting any value set previously
+    pub fn set_search(&mut self, search: Vec<String>) {
+        self.search = search;
+    }
+
+    /// The `options` tokens this config would render, e.g. `["ndots:2", "rotate"]`
+    fn option_tokens(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if self.debug {
+            options.push("debug".to_string());
+        }
+        if self.ndots != 1 {
+            options.push(format!("ndots:{}", self.ndots));
+        }
+        if self.timeout != 5 {
+            options.push(format!("timeout:{}", self.timeout));
+        }
+        if self.attempts != 2 {
+            options.push(format!("attempts:{}", self.attempts));
+        }
+        if self.rotate {
+            options.push("rotate".to_string());
+        }
+        if self.no_check_names {
+            options.push("no-check-names".to_string());
+        }
+        if self.inet6 {
+            options.push("inet6".to_string());
+        }
+        if self.ip6_bytestring {
+            options.push("ip6-bytestring".to_string());
+        }
+        if self.ip6_dotint {
+            options.push("ip6-dotint".to_string());
+        }
+        if self.edns0 {
+            options.push("edns0".to_string());
+        }
+        if self.single_request {
+            options.push("single-request".to_string());
+        }
+        if self.single_request_reopen {
+            options.push("single-request-reopen".to_string());
+        }
+        if self.no_reload {
+            options.push("no-reload".to_string());
+        }
+        if self.trust_ad {
+            options.push("trust-ad".to_string());
+        }
+        if self.no_tld_query {
+            options.push("no-tld-query".to_string());
+        }
+        if self.use_vc {
+            options.push("use-vc".to_string());
+        }
+        options
+    }
+}
+
+impl fmt::Display for Config {
+    /// Render the config back into `resolv.conf` syntax.
+    ///
+    /// The output is a normal form: each directive appears at most once
+    /// (`nameserver` lines aside), but `Config::parse(config.to_string())`
+    /// always yields back an equal `Config`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(domain) = &self.domain {
+            writeln!(f, "domain {domain}")?;
+        }
+        if !self.search.is_empty() {
+            writeln!(f, "search {}", self.search.join(" "))?;
+        }
+        for ns in &self.nameservers {
+            writeln!(f, "nameserver {ns}")?;
+        }
+        if !self.sortlist.is_empty() {
+            let networks: Vec<String> = self.sortlist.iter().map(|n| n.to_string()).collect();
+            writeln!(f, "sortlist {}", networks.join(" "))?;
+        }
+        if !self.lookup.is_empty() {
+            let sources: Vec<&str> = self
+                .lookup
+                .iter()
+                .map(|l| match l {
+                    Lookup::File => "file",
+                    Lookup::Bind => "bind",
+                    Lookup::Extra(s) => s.as_str(),
+                })
+                .collect();
+            writeln!(f, "lookup {}", sources.join(" "))?;
+        }
+        if !self.family.is_empty() {
+            let families: Vec<&str> = self
+                .family
+                .iter()
+                .map(|fam| match fam {
+                    Family::Inet4 => "inet4",
+                    Family::Inet6 => "inet6",
+                })
+                .collect();
+            writeln!(f, "family {}", families.join(" "))?;
+        }
+        let options = self.option_tokens();
+        if !options.is_empty() {
+            writeln!(f, "options {}", options.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+
+    #[test]
+    fn parse_write_parse_round_trip() {
+        let cfg = Config {
+            nameservers: vec![
+                Ip::V4(Ipv4Addr::new(8, 8, 8, 8), Some(53)),
+                Ip::V6(
+                    Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
+                    Some(1),
+                    Some(5353),
+                ),
+            ],
+            domain: Some("example.com".to_string()),
+            search: vec!["example.com".to_string(), "sub.example.com".to_string()],
+            sortlist: vec![Network::V4(
+                Ipv4Addr::new(130, 155, 160, 0),
+                Ipv4Addr::new(255, 255, 240, 0),
+            )],
+            debug: true,
+            ndots: 4,
+            timeout: 7,
+            attempts: 3,
+            rotate: true,
+            no_check_names: true,
+            inet6: true,
+            ip6_bytestring: true,
+            ip6_dotint: true,
+            edns0: true,
+            single_request: true,
+            single_request_reopen: true,
+            no_reload: true,
+            trust_ad: true,
+            no_tld_query: true,
+            use_vc: true,
+            lookup: vec![Lookup::File, Lookup::Bind, Lookup::Extra("wins".to_string())],
+            family: vec![Family::Inet4, Family::Inet6],
+        };
+
+        let rendered = cfg.to_string();
+        let reparsed =
+            Config::parse(rendered.as_bytes()).expect("rendered config should re-parse");
+        assert_eq!(
+            cfg, reparsed,
+            "round trip through Display/parse changed the config:\n{rendered}"
+        );
+    }
+
+    #[test]
+    fn default_config_round_trips_to_an_empty_file() {
+        let cfg = Config::default();
+        assert_eq!(cfg.to_string(), "");
+        assert_eq!(Config::parse(cfg.to_string()).unwrap(), cfg);
+    }
+
+    #[test]
+    fn apply_options_overlays_localdomain_and_res_options() {
+        let mut cfg = Config::parse("search example.com\noptions ndots:1\n").unwrap();
+        cfg.apply_options(
+            Some("override.example sub.override.example".to_string()),
+            Some("ndots:3 rotate".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            cfg.search,
+            vec![
+                "override.example".to_string(),
+                "sub.override.example".to_string()
+            ]
+        );
+        assert_eq!(cfg.ndots, 3);
+        assert!(cfg.rotate);
+    }
+
+    #[test]
+    fn apply_options_reports_a_bad_res_options_token_without_implying_a_line() {
+        let mut cfg = Config::default();
+        let err = cfg
+            .apply_options(None, Some("ndots:not-a-number".to_string()))
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ParseError::InvalidEnvironmentOption { var: "RES_OPTIONS" }
+        ));
+        assert_eq!(err.to_string(), "RES_OPTIONS contains invalid value of some option");
+    }
+
+    #[test]
+    fn parse_strict_rejects_a_bad_domain() {
+        let raw = "domain bad..example\n";
+        assert!(matches!(
+            Config::parse_strict(raw).unwrap_err(),
+            ParseError::InvalidHostname { line: 0 }
+        ));
+        assert!(Config::parse(raw).is_ok());
+    }
+
+    #[test]
+    fn parse_strict_rejects_a_bad_search_entry() {
+        let raw = "search good.example bad..example\n";
+        assert!(matches!(
+            Config::parse_strict(raw).unwrap_err(),
+            ParseError::InvalidHostname { line: 0 }
+        ));
+        assert!(Config::parse(raw).is_ok());
+    }
+
+    #[test]
+    fn parse_strict_accepts_a_fully_valid_config() {
+        let raw = "domain example.com\nsearch example.com sub.example.com\nnameserver 8.8.8.8\n";
+        let strict = Config::parse_strict(raw).expect("valid config should pass strict parsing");
+        assert_eq!(strict, Config::parse(raw).unwrap());
+    }
+}
@@ -3,6 +3,7 @@ use std::net::{Ipv4Addr, Ipv6Addr};
 use std::str::{Utf8Error, from_utf8};
 
 use super::{AddrParseError, Config, Network, Lookup, Family};
+use crate::hostname;
 
 /// Error while parsing resolv.conf file
 #[allow(missing_docs)]
@@ -30,6 +31,15 @@ pub enum ParseError {
 
     /// Error returned when there is extra data at the end of a line.
     ExtraData{ line: usize },
+
+    /// Error returned when a `domain` or `search` entry is not a valid
+    /// hostname. Only produced by `Config::parse_strict`.
+    InvalidHostname{ line: usize },
+
+    /// Error returned when an `options` token from the `RES_OPTIONS`
+    /// environment variable is invalid. Unlike the other variants, this
+    /// isn't tied to a line in a file, since there is none.
+    InvalidEnvironmentOption { var: &'static str },
 }
 
 impl std::fmt::Display for ParseError {
@@ -56,6 +66,12 @@ impl std::fmt::Display for ParseError {
             ParseError::ExtraData { line } => {
                 write!(f, "extra data at the end of the line {line}")
             }
+            ParseError::InvalidHostname { line } => {
+                write!(f, "directive at line {line} contains an invalid hostname")
+            }
+            ParseError::InvalidEnvironmentOption { var } => {
+                write!(f, "{var} contains invalid value of some option")
+            }
         }
     }
 }
@@ -130,121 +146,231 @@ fn ip_v6_netw(val: &str) -> Result<Network, AddrParseError> {
     }
 }
 
-pub(crate) fn parse(bytes: &[u8]) -> Result<Config, ParseError> {
-    let mut cfg = Config::default();
-    'lines: for (line, content) in bytes.split(|&x| x == b'\n').enumerate() {
-        for &c in content.iter() {
-            if c != b'\t' && c != b' ' {
-                if c == b';' || c == b'#' {
-                    continue 'lines;
-                } else {
-                    break;
-                }
+/// Parse a single `sortlist` entry, e.g. `130.155.160.0/255.255.240.0` or `2001:db8::/32`
+pub(crate) fn parse_network(val: &str) -> Result<Network, AddrParseError> {
+    ip_v4_netw(val).or_else(|_| ip_v6_netw(val))
+}
+
+/// Whether `content` is a comment line (`;` or `#` as the first
+/// non-whitespace character), and can be skipped without even looking at its
+/// encoding. Blank lines are not comment lines and yield `false`.
+fn is_comment_line(content: &[u8]) -> bool {
+    for &c in content.iter() {
+        if c != b'\t' && c != b' ' {
+            return c == b';' || c == b'#';
+        }
+    }
+    false
+}
+
+/// Apply a single `options` token (e.g. `ndots:3` or `rotate`) to `cfg`.
+///
+/// This is the one place that understands `options` token syntax, shared by
+/// the `options` arm of `parse_line` and by `Config::apply_environment`
+/// (which applies `RES_OPTIONS` tokens the same way).
+pub(crate) fn apply_option_token(cfg: &mut Config, pair: &str, line: usize) -> Result<(), ParseError> {
+    let mut iter = pair.splitn(2, ':');
+    let key = iter.next().unwrap();
+    let value = iter.next();
+    if iter.next().is_some() {
+        return Err(ParseError::ExtraData { line });
+    }
+    match (key, value) {
+        // TODO(tailhook) ensure that values are None?
+        ("debug", _) => cfg.debug = true,
+        ("ndots", Some(x)) => {
+            cfg.ndots = x.parse().map_err(|_| ParseError::InvalidOptionValue { line })?
+        }
+        ("timeout", Some(x)) => {
+            cfg.timeout = x.parse().map_err(|_| ParseError::InvalidOptionValue { line })?
+        }
+        ("attempts", Some(x)) => {
+            cfg.attempts = x.parse().map_err(|_| ParseError::InvalidOptionValue { line })?
+        }
+        ("rotate", _) => cfg.rotate = true,
+        ("no-check-names", _) => cfg.no_check_names = true,
+        ("inet6", _) => cfg.inet6 = true,
+        ("ip6-bytestring", _) => cfg.ip6_bytestring = true,
+        ("ip6-dotint", _) => cfg.ip6_dotint = true,
+        ("no-ip6-dotint", _) => cfg.ip6_dotint = false,
+        ("edns0", _) => cfg.edns0 = true,
+        ("single-request", _) => cfg.single_request = true,
+        ("single-request-reopen", _) => cfg.single_request_reopen = true,
+        ("no-reload", _) => cfg.no_reload = true,
+        ("trust-ad", _) => cfg.trust_ad = true,
+        ("no-tld-query", _) => cfg.no_tld_query = true,
+        ("use-vc", _) => cfg.use_vc = true,
+        _ => return Err(ParseError::InvalidOption { line }),
+    }
+    Ok(())
+}
+
+fn parse_line(
+    cfg: &mut Config,
+    line: usize,
+    content: &[u8],
+    validate_hostnames: bool,
+) -> Result<(), ParseError> {
+    let mut words = from_utf8(content)
+        .map_err(|err| ParseError::InvalidUtf8 { line, err })?
+        // ignore everything after ';' or '#'
+        .split([';', '#'])
+        .next()
+        .ok_or(ParseError::InvalidValue { line })?
+        .split_whitespace();
+    let keyword = match words.next() {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    match keyword {
+        "nameserver" => {
+            let srv = words
+                .next()
+                .ok_or(ParseError::InvalidValue { line })
+                .map(|addr| addr.parse().map_err(|err| ParseError::InvalidIp {line, err }))??;
+            cfg.nameservers.push(srv);
+            if words.next().is_some() {
+                return Err(ParseError::ExtraData { line });
             }
         }
-        // All that dances above to allow invalid utf-8 inside the comments
-        let mut words = from_utf8(content)
-            .map_err(|err| ParseError::InvalidUtf8 { line, err })?
-            // ignore everything after ';' or '#'
-            .split([';', '#'])
-            .next()
-            .ok_or(ParseError::InvalidValue { line })?
-            .split_whitespace();
-        let keyword = match words.next() {
-            Some(x) => x,
-            None => continue,
-        };
-        match keyword {
-            "nameserver" => {
-                let srv = words
-                    .next()
-                    .ok_or(ParseError::InvalidValue { line })
-                    .map(|addr| addr.parse().map_err(|err| ParseError::InvalidIp {line, err }))??;
-                cfg.nameservers.push(srv);
-                if words.next().is_some() {
-                    return Err(ParseError::ExtraData { line });
-                }
+        "domain" => {
+            let dom: String = words
+                .next()
+                .and_then(|x| x.parse().ok())
+                .ok_or(ParseError::InvalidValue { line })?;
+            if validate_hostnames && !hostname::is_valid_hostname(&dom) {
+                return Err(ParseError::InvalidHostname { line });
             }
-            "domain" => {
-                let dom = words
-                    .next()
-                    .and_then(|x| x.parse().ok())
-                    .ok_or(ParseError::InvalidValue { line })?;
-                cfg.set_domain(dom);
-                if words.next().is_some() {
-                    return Err(ParseError::ExtraData { line });
-                }
+            cfg.set_domain(dom);
+            if words.next().is_some() {
+                return Err(ParseError::ExtraData { line });
             }
-            "search" => {
-                cfg.set_search(words.map(|x| x.to_string()).collect());
+        }
+        "search" => {
+            let names: Vec<String> = words.map(|x| x.to_string()).collect();
+            if validate_hostnames && names.iter().any(|n| !hostname::is_valid_hostname(n)) {
+                return Err(ParseError::InvalidHostname { line });
             }
-            "sortlist" => {
-                cfg.sortlist.clear();
-                for pair in words {
-                    let netw = ip_v4_netw(pair)
-                        .or_else(|_| ip_v6_netw(pair))
-                        .map_err(|err| ParseError::InvalidIp { line, err })?;
-                    cfg.sortlist.push(netw);
-                }
+            cfg.set_search(names);
+        }
+        "sortlist" => {
+            cfg.sortlist.clear();
+            for pair in words {
+                let netw = parse_network(pair).map_err(|err| ParseError::InvalidIp { line, err })?;
+                cfg.sortlist.push(netw);
             }
-            "options" => {
-                for pair in words {
-                    let mut iter = pair.splitn(2, ':');
-                    let key = iter.next().unwrap();
-                    let value = iter.next();
-                    if iter.next().is_some() {
-                        return Err(ParseError::ExtraData { line });
-                    }
-                    match (key, value) {
-                        // TODO(tailhook) ensure that values are None?
-                        ("debug", _) => cfg.debug = true,
-                        ("ndots", Some(x)) => {
-                            cfg.ndots = x.parse().map_err(|_| ParseError::InvalidOptionValue { line })?
-                        }
-                        ("timeout", Some(x)) => {
-                            cfg.timeout = x.parse().map_err(|_| ParseError::InvalidOptionValue { line })?
-                        }
-                        ("attempts", Some(x)) => {
-                            cfg.attempts = x.parse().map_err(|_| ParseError::InvalidOptionValue { line })?
-                        }
-                        ("rotate", _) => cfg.rotate = true,
-                        ("no-check-names", _) => cfg.no_check_names = true,
-                        ("inet6", _) => cfg.inet6 = true,
-                        ("ip6-bytestring", _) => cfg.ip6_bytestring = true,
-                        ("ip6-dotint", _) => cfg.ip6_dotint = true,
-                        ("no-ip6-dotint", _) => cfg.ip6_dotint = false,
-                        ("edns0", _) => cfg.edns0 = true,
-                        ("single-request", _) => cfg.single_request = true,
-                        ("single-request-reopen", _) => cfg.single_request_reopen = true,
-                        ("no-reload", _) => cfg.no_reload = true,
-                        ("trust-ad", _) => cfg.trust_ad = true,
-                        ("no-tld-query", _) => cfg.no_tld_query = true,
-                        ("use-vc", _) => cfg.use_vc = true,
-                        _ => return Err(ParseError::InvalidOption { line }),
-                    }
-                }
+        }
+        "options" => {
+            for pair in words {
+                apply_option_token(cfg, pair, line)?;
             }
-            "lookup" => {
-                for word in words {
-                    match word {
-                        "file" => cfg.lookup.push(Lookup::File),
-                        "bind" => cfg.lookup.push(Lookup::Bind),
-                        extra => cfg.lookup.push(Lookup::Extra(extra.to_string())),
-                    }
+        }
+        "lookup" => {
+            for word in words {
+                match word {
+                    "file" => cfg.lookup.push(Lookup::File),
+                    "bind" => cfg.lookup.push(Lookup::Bind),
+                    extra => cfg.lookup.push(Lookup::Extra(extra.to_string())),
                 }
             }
-            "family" => {
-                for word in words {
-                    match word {
-                        "inet4" => cfg.family.push(Family::Inet4),
-                        "inet6" => cfg.family.push(Family::Inet6),
-                        _ => return Err(ParseError::InvalidValue { line }),
-                    }
+        }
+        "family" => {
+            for word in words {
+                match word {
+                    "inet4" => cfg.family.push(Family::Inet4),
+                    "inet6" => cfg.family.push(Family::Inet6),
+                    _ => return Err(ParseError::InvalidValue { line }),
                 }
             }
-            _ => return Err(ParseError::InvalidDirective { line }),
         }
+        _ => return Err(ParseError::InvalidDirective { line }),
     }
 
+    Ok(())
+}
+
+/// Parse `bytes` into a `Config`, stopping at the first error.
+pub(crate) fn parse(bytes: &[u8]) -> Result<Config, ParseError> {
+    parse_with(bytes, false)
+}
+
+/// Like `parse`, but also rejects `domain`/`search` entries that aren't
+/// valid RFC 1035 hostnames.
+pub(crate) fn parse_strict(bytes: &[u8]) -> Result<Config, ParseError> {
+    parse_with(bytes, true)
+}
+
+fn parse_with(bytes: &[u8], validate_hostnames: bool) -> Result<Config, ParseError> {
+    let mut cfg = Config::default();
+    for (line, content) in bytes.split(|&x| x == b'\n').enumerate() {
+        if is_comment_line(content) {
+            continue;
+        }
+        parse_line(&mut cfg, line, content, validate_hostnames)?;
+    }
     Ok(cfg)
 }
+
+/// Parse `bytes` into a `Config`, recording every recoverable `ParseError`
+/// instead of stopping at the first one.
+///
+/// A malformed line doesn't abort the whole parse: parsing goes on with the
+/// next line, so the returned `Config` reflects every directive that could be
+/// understood. Whatever a line applied before its error was found is kept
+/// (e.g. the address of a `nameserver` line with trailing extra data).
+/// `ParseError::InvalidUtf8` is still fatal, since there is no meaningful way
+/// to resynchronize on the next line once the byte stream is off track.
+pub(crate) fn parse_lenient(bytes: &[u8]) -> (Config, Vec<ParseError>) {
+    let mut cfg = Config::default();
+    let mut errors = Vec::new();
+    for (line, content) in bytes.split(|&x| x == b'\n').enumerate() {
+        if is_comment_line(content) {
+            continue;
+        }
+        if let Err(err) = parse_line(&mut cfg, line, content, false) {
+            let fatal = matches!(err, ParseError::InvalidUtf8 { .. });
+            errors.push(err);
+            if fatal {
+                break;
+            }
+        }
+    }
+    (cfg, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lenient_skips_bad_lines_but_keeps_the_good_ones() {
+        let buf = b"\
+nameserver 8.8.8.8
+options ndots:bad
+unknowndirective foo
+nameserver not-an-ip
+domain example.com
+";
+        let (cfg, errors) = parse_lenient(buf);
+
+        assert_eq!(cfg.nameservers, vec!["8.8.8.8".parse().unwrap()]);
+        assert_eq!(cfg.domain, Some("example.com".to_string()));
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], ParseError::InvalidOptionValue { line: 1 }));
+        assert!(matches!(errors[1], ParseError::InvalidDirective { line: 2 }));
+        assert!(matches!(errors[2], ParseError::InvalidIp { line: 3, .. }));
+    }
+
+    #[test]
+    fn lenient_records_invalid_utf8_and_stops_there() {
+        let mut buf = b"nameserver 8.8.8.8\n".to_vec();
+        buf.extend_from_slice(b"domain \xff\xfe\n");
+        buf.extend_from_slice(b"nameserver 9.9.9.9\n");
+
+        let (cfg, errors) = parse_lenient(&buf);
+
+        assert_eq!(cfg.nameservers, vec!["8.8.8.8".parse().unwrap()]);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], ParseError::InvalidUtf8 { line: 1, .. }));
+    }
+}
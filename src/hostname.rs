@@ -0,0 +1,97 @@
+//! RFC 1035-style hostname validation for `domain`/`search` entries.
+//!
+//! This mirrors the loose rules rustls-pki-types uses to validate DNS names:
+//! ASCII only, each label made of letters/digits/hyphen/underscore with no
+//! leading or trailing hyphen, and the whole name rejected if it's nothing
+//! but digits and dots (so it can't be confused with an IP literal).
+
+/// Whether `name` is a valid hostname, as used in a `domain` or `search`
+/// directive. A single optional trailing dot is allowed to mark a
+/// fully-qualified name.
+pub(crate) fn is_valid_hostname(name: &str) -> bool {
+    if !name.is_ascii() || name.is_empty() || name.len() > 253 {
+        return false;
+    }
+
+    let name = name.strip_suffix('.').unwrap_or(name);
+    if name.is_empty() {
+        return false;
+    }
+
+    if name.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        // purely numeric (or numeric with dots): looks like an IP literal,
+        // not a hostname
+        return false;
+    }
+
+    name.split('.').all(is_valid_label)
+}
+
+fn is_valid_label(label: &str) -> bool {
+    if label.is_empty() || label.len() > 63 {
+        return false;
+    }
+    if label.starts_with('-') || label.ends_with('-') {
+        return false;
+    }
+    label
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_a_253_byte_name() {
+        // 4 labels of 63 bytes joined by dots: 63 * 4 + 3 == 255, so use
+        // 3 full labels plus one 61-byte label to land exactly on 253.
+        let name = format!("{}.{}.{}.{}", "a".repeat(63), "a".repeat(63), "a".repeat(63), "a".repeat(61));
+        assert_eq!(name.len(), 253);
+        assert!(is_valid_hostname(&name));
+    }
+
+    #[test]
+    fn rejects_a_254_byte_name() {
+        let name = format!("{}.{}.{}.{}", "a".repeat(63), "a".repeat(63), "a".repeat(63), "a".repeat(62));
+        assert_eq!(name.len(), 254);
+        assert!(!is_valid_hostname(&name));
+    }
+
+    #[test]
+    fn accepts_a_63_byte_label() {
+        assert!(is_valid_hostname(&"a".repeat(63)));
+    }
+
+    #[test]
+    fn rejects_a_64_byte_label() {
+        assert!(!is_valid_hostname(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn rejects_purely_numeric_names_as_ip_literals() {
+        assert!(!is_valid_hostname("1.2.3.4"));
+    }
+
+    #[test]
+    fn accepts_a_name_that_merely_has_an_all_digit_label() {
+        assert!(is_valid_hostname("v4.1"));
+    }
+
+    #[test]
+    fn rejects_leading_and_trailing_hyphens() {
+        assert!(!is_valid_hostname("-bad"));
+        assert!(!is_valid_hostname("bad-"));
+    }
+
+    #[test]
+    fn rejects_empty_labels() {
+        assert!(!is_valid_hostname("a..b"));
+    }
+
+    #[test]
+    fn accepts_a_single_trailing_dot() {
+        assert!(is_valid_hostname("a.com."));
+    }
+}
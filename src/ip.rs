@@ -0,0 +1,232 @@
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::str::FromStr;
+
+/// Error returned when a string cannot be parsed as an IP address or network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddrParseError;
+
+impl fmt::Display for AddrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error parsing IP address")
+    }
+}
+
+impl std::error::Error for AddrParseError {}
+
+impl From<std::net::AddrParseError> for AddrParseError {
+    fn from(_: std::net::AddrParseError) -> AddrParseError {
+        AddrParseError
+    }
+}
+
+impl From<std::num::ParseIntError> for AddrParseError {
+    fn from(_: std::num::ParseIntError) -> AddrParseError {
+        AddrParseError
+    }
+}
+
+/// A nameserver address, as found in a `nameserver` directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ip {
+    /// An IPv4 address, with an optional port (e.g. `8.8.8.8:53`)
+    V4(Ipv4Addr, Option<u16>),
+    /// An IPv6 address, with an optional scope id (e.g. `fe80::1%eth0` is
+    /// written as `fe80::1%1` in `resolv.conf`) and an optional port (written
+    /// bracketed, e.g. `[2001:db8::1]:53`)
+    V6(Ipv6Addr, Option<u32>, Option<u16>),
+}
+
+impl Ip {
+    /// This address as a `SocketAddr`, using `default_port` when the
+    /// directive didn't specify one.
+    pub fn to_socket_addr(self, default_port: u16) -> SocketAddr {
+        match self {
+            Ip::V4(addr, port) => {
+                SocketAddr::V4(SocketAddrV4::new(addr, port.unwrap_or(default_port)))
+            }
+            Ip::V6(addr, scope, port) => SocketAddr::V6(SocketAddrV6::new(
+                addr,
+                port.unwrap_or(default_port),
+                0,
+                scope.unwrap_or(0),
+            )),
+        }
+    }
+}
+
+/// Parse a bare (unbracketed) IPv6 address with an optional `%scope` suffix.
+fn parse_v6_with_scope(s: &str) -> Result<(Ipv6Addr, Option<u32>), AddrParseError> {
+    match s.split_once('%') {
+        Some((addr, scope)) => Ok((addr.parse()?, Some(scope.parse()?))),
+        None => Ok((s.parse()?, None)),
+    }
+}
+
+impl FromStr for Ip {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<Ip, AddrParseError> {
+        // Bracketed IPv6, e.g. `[2001:db8::1]:53` or `[2001:db8::1%1]:53`.
+        if let Some(rest) = s.strip_prefix('[') {
+            let (inner, after) = rest.split_once(']').ok_or(AddrParseError)?;
+            let port = match after.strip_prefix(':') {
+                Some(p) => Some(p.parse::<u16>()?),
+                None if after.is_empty() => None,
+                None => return Err(AddrParseError),
+            };
+            let (addr, scope) = parse_v6_with_scope(inner)?;
+            return Ok(Ip::V6(addr, scope, port));
+        }
+
+        // `addr:port` is only recognized for IPv4; an unbracketed IPv6
+        // address may contain colons of its own.
+        if let Some((addr, port)) = s.rsplit_once(':') {
+            if let Ok(addr) = addr.parse::<Ipv4Addr>() {
+                return Ok(Ip::V4(addr, Some(port.parse::<u16>()?)));
+            }
+        }
+        if let Ok(addr) = s.parse::<Ipv4Addr>() {
+            return Ok(Ip::V4(addr, None));
+        }
+
+        let (addr, scope) = parse_v6_with_scope(s)?;
+        Ok(Ip::V6(addr, scope, None))
+    }
+}
+
+impl fmt::Display for Ip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ip::V4(addr, None) => write!(f, "{addr}"),
+            Ip::V4(addr, Some(port)) => write!(f, "{addr}:{port}"),
+            Ip::V6(addr, scope, None) => match scope {
+                Some(scope) => write!(f, "{addr}%{scope}"),
+                None => write!(f, "{addr}"),
+            },
+            Ip::V6(addr, scope, Some(port)) => match scope {
+                Some(scope) => write!(f, "[{addr}%{scope}]:{port}"),
+                None => write!(f, "[{addr}]:{port}"),
+            },
+        }
+    }
+}
+
+/// A network (address + mask), as found in a `sortlist` directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    /// An IPv4 network
+    V4(Ipv4Addr, Ipv4Addr),
+    /// An IPv6 network
+    V6(Ipv6Addr, Ipv6Addr),
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::V4(addr, mask) => write!(f, "{addr}/{mask}"),
+            Network::V6(addr, mask) => write!(f, "{addr}/{mask}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_v4() {
+        assert_eq!(
+            "8.8.8.8".parse::<Ip>().unwrap(),
+            Ip::V4(Ipv4Addr::new(8, 8, 8, 8), None)
+        );
+    }
+
+    #[test]
+    fn parses_v4_with_port() {
+        assert_eq!(
+            "8.8.8.8:53".parse::<Ip>().unwrap(),
+            Ip::V4(Ipv4Addr::new(8, 8, 8, 8), Some(53))
+        );
+    }
+
+    #[test]
+    fn parses_bare_v6() {
+        assert_eq!(
+            "2001:db8::1".parse::<Ip>().unwrap(),
+            Ip::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1), None, None)
+        );
+    }
+
+    #[test]
+    fn parses_v6_with_scope() {
+        assert_eq!(
+            "fe80::1%2".parse::<Ip>().unwrap(),
+            Ip::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1), Some(2), None)
+        );
+    }
+
+    #[test]
+    fn parses_bracketed_v6_with_port() {
+        assert_eq!(
+            "[2001:db8::1]:53".parse::<Ip>().unwrap(),
+            Ip::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1), None, Some(53))
+        );
+    }
+
+    #[test]
+    fn parses_bracketed_v6_with_scope_and_port() {
+        assert_eq!(
+            "[fe80::1%2]:53".parse::<Ip>().unwrap(),
+            Ip::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1), Some(2), Some(53))
+        );
+    }
+
+    #[test]
+    fn reads_unbracketed_v6_with_port_as_a_bare_address() {
+        // `2001:db8::1:53` is ambiguous with a bare IPv6 address, so a port
+        // is only recognized when the address is bracketed.
+        assert_eq!(
+            "2001:db8::1:53".parse::<Ip>().unwrap(),
+            Ip::V6(
+                Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 1, 0x53),
+                None,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!("not-an-ip".parse::<Ip>().is_err());
+        assert!("[2001:db8::1".parse::<Ip>().is_err());
+        assert!("8.8.8.8:not-a-port".parse::<Ip>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips_every_form() {
+        for text in [
+            "8.8.8.8",
+            "8.8.8.8:53",
+            "2001:db8::1",
+            "fe80::1%2",
+            "[2001:db8::1]:53",
+            "[fe80::1%2]:53",
+        ] {
+            let ip: Ip = text.parse().unwrap();
+            assert_eq!(ip.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn to_socket_addr_falls_back_to_the_default_port() {
+        let ip: Ip = "8.8.8.8".parse().unwrap();
+        assert_eq!(ip.to_socket_addr(53).to_string(), "8.8.8.8:53");
+
+        let ip: Ip = "8.8.8.8:5353".parse().unwrap();
+        assert_eq!(ip.to_socket_addr(53).to_string(), "8.8.8.8:5353");
+
+        let ip: Ip = "fe80::1%2".parse().unwrap();
+        assert_eq!(ip.to_socket_addr(53).to_string(), "[fe80::1%2]:53");
+    }
+}
@@ -32,11 +32,12 @@
 //!     // We can build configs manually as well, either directly or with Config::new()
 //!     let expected_config = Config {
 //!         nameservers: vec![
-//!             Ip::V6(Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888), None),
-//!             Ip::V6(Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8844), None),
-//!             Ip::V4(Ipv4Addr::new(8, 8, 8, 8)),
-//!             Ip::V4(Ipv4Addr::new(8, 8, 4, 4)),
+//!             Ip::V6(Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888), None, None),
+//!             Ip::V6(Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8844), None, None),
+//!             Ip::V4(Ipv4Addr::new(8, 8, 8, 8), None),
+//!             Ip::V4(Ipv4Addr::new(8, 8, 4, 4), None),
 //!         ],
+//!         domain: Some(String::from("example.com")),
 //!         search: vec![String::from("example.com"), String::from("sub.example.com")],
 //!         sortlist: vec![
 //!             Network::V4(Ipv4Addr::new(130, 155, 160, 0), Ipv4Addr::new(255, 255, 240, 0)),
@@ -54,8 +55,12 @@
 //!         edns0: false,
 //!         single_request: false,
 //!         single_request_reopen: false,
+//!         no_reload: false,
+//!         trust_ad: false,
 //!         no_tld_query: true,
 //!         use_vc: false,
+//!         lookup: Vec::new(),
+//!         family: Vec::new(),
 //!     };
 //!
 //!     // We can compare configurations, since resolv_conf::Config implements Eq
@@ -88,13 +93,13 @@
 #![warn(missing_debug_implementations)]
 #![warn(missing_docs)]
 
-#[macro_use]
-extern crate quick_error;
-
 mod grammar;
+mod hostname;
 mod ip;
 mod config;
+#[cfg(feature = "serde")]
+mod serde_impl;
 
 pub use grammar::ParseError;
 pub use ip::{AddrParseError, Ip, Network};
-pub use config::Config;
+pub use config::{Config, Family, Lookup};
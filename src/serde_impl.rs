@@ -0,0 +1,321 @@
+//! `serde` support for [`Config`], gated behind the `serde` feature.
+//!
+//! Following the approach the Proxmox config crates use for section-style
+//! files, a `Config` is never derived on directly. Instead the raw directives
+//! are first shaped into an intermediate [`ConfigMap`] (itself plain
+//! `Serialize`/`Deserialize` data), which is then converted to and from a
+//! `Config`. This keeps the wire format a plain, self-describing document
+//! (`options` becomes named fields instead of a flat token list) while
+//! `Config::parse` remains the single place that understands `resolv.conf`
+//! syntax.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, Family, Lookup};
+use crate::grammar;
+use crate::ip::{Ip, Network};
+
+/// Structured form of the `options` directive: one named field per option,
+/// rather than a flat list of `key[:value]` tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct OptionsMap {
+    #[serde(default)]
+    debug: bool,
+    #[serde(default = "default_ndots")]
+    ndots: u32,
+    #[serde(default = "default_timeout")]
+    timeout: u32,
+    #[serde(default = "default_attempts")]
+    attempts: u32,
+    #[serde(default)]
+    rotate: bool,
+    #[serde(default)]
+    no_check_names: bool,
+    #[serde(default)]
+    inet6: bool,
+    #[serde(default)]
+    ip6_bytestring: bool,
+    #[serde(default)]
+    ip6_dotint: bool,
+    #[serde(default)]
+    edns0: bool,
+    #[serde(default)]
+    single_request: bool,
+    #[serde(default)]
+    single_request_reopen: bool,
+    #[serde(default)]
+    no_reload: bool,
+    #[serde(default)]
+    trust_ad: bool,
+    #[serde(default)]
+    no_tld_query: bool,
+    #[serde(default)]
+    use_vc: bool,
+}
+
+fn default_ndots() -> u32 {
+    1
+}
+
+fn default_timeout() -> u32 {
+    5
+}
+
+fn default_attempts() -> u32 {
+    2
+}
+
+impl Default for OptionsMap {
+    fn default() -> OptionsMap {
+        OptionsMap {
+            debug: false,
+            ndots: default_ndots(),
+            timeout: default_timeout(),
+            attempts: default_attempts(),
+            rotate: false,
+            no_check_names: false,
+            inet6: false,
+            ip6_bytestring: false,
+            ip6_dotint: false,
+            edns0: false,
+            single_request: false,
+            single_request_reopen: false,
+            no_reload: false,
+            trust_ad: false,
+            no_tld_query: false,
+            use_vc: false,
+        }
+    }
+}
+
+/// The intermediate, directive-keyed representation of a [`Config`].
+///
+/// `nameservers` and `sortlist` entries are the same textual form they'd take
+/// in `resolv.conf` (e.g. `"8.8.8.8"`, `"130.155.160.0/255.255.240.0"`), so
+/// the structured and raw-string forms stay interchangeable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigMap {
+    #[serde(default)]
+    nameservers: Vec<String>,
+    #[serde(default)]
+    domain: Option<String>,
+    #[serde(default)]
+    search: Vec<String>,
+    #[serde(default)]
+    sortlist: Vec<String>,
+    #[serde(default)]
+    options: OptionsMap,
+    #[serde(default)]
+    lookup: Vec<String>,
+    #[serde(default)]
+    family: Vec<String>,
+}
+
+/// Error converting a [`ConfigMap`] (as deserialized from the structured
+/// form) into a [`Config`].
+#[derive(Debug)]
+enum ConfigMapError {
+    NameserverAddr(String),
+    SortlistNetwork(String),
+    UnknownFamily(String),
+}
+
+impl fmt::Display for ConfigMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigMapError::NameserverAddr(s) => write!(f, "invalid nameserver address: {s}"),
+            ConfigMapError::SortlistNetwork(s) => write!(f, "invalid sortlist network: {s}"),
+            ConfigMapError::UnknownFamily(s) => write!(f, "invalid family: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigMapError {}
+
+impl TryFrom<ConfigMap> for Config {
+    type Error = ConfigMapError;
+
+    fn try_from(map: ConfigMap) -> Result<Config, ConfigMapError> {
+        let nameservers = map
+            .nameservers
+            .iter()
+            .map(|s| {
+                s.parse::<Ip>()
+                    .map_err(|_| ConfigMapError::NameserverAddr(s.clone()))
+            })
+            .collect::<Result<Vec<Ip>, _>>()?;
+        let sortlist = map
+            .sortlist
+            .iter()
+            .map(|s| {
+                grammar::parse_network(s).map_err(|_| ConfigMapError::SortlistNetwork(s.clone()))
+            })
+            .collect::<Result<Vec<Network>, _>>()?;
+        let lookup = map
+            .lookup
+            .iter()
+            .map(|s| match s.as_str() {
+                "file" => Lookup::File,
+                "bind" => Lookup::Bind,
+                other => Lookup::Extra(other.to_string()),
+            })
+            .collect();
+        let family = map
+            .family
+            .iter()
+            .map(|s| match s.as_str() {
+                "inet4" => Ok(Family::Inet4),
+                "inet6" => Ok(Family::Inet6),
+                other => Err(ConfigMapError::UnknownFamily(other.to_string())),
+            })
+            .collect::<Result<Vec<Family>, _>>()?;
+
+        Ok(Config {
+            nameservers,
+            domain: map.domain,
+            search: map.search,
+            sortlist,
+            debug: map.options.debug,
+            ndots: map.options.ndots,
+            timeout: map.options.timeout,
+            attempts: map.options.attempts,
+            rotate: map.options.rotate,
+            no_check_names: map.options.no_check_names,
+            inet6: map.options.inet6,
+            ip6_bytestring: map.options.ip6_bytestring,
+            ip6_dotint: map.options.ip6_dotint,
+            edns0: map.options.edns0,
+            single_request: map.options.single_request,
+            single_request_reopen: map.options.single_request_reopen,
+            no_reload: map.options.no_reload,
+            trust_ad: map.options.trust_ad,
+            no_tld_query: map.options.no_tld_query,
+            use_vc: map.options.use_vc,
+            lookup,
+            family,
+        })
+    }
+}
+
+impl From<&Config> for ConfigMap {
+    fn from(cfg: &Config) -> ConfigMap {
+        ConfigMap {
+            nameservers: cfg.nameservers.iter().map(|ip| ip.to_string()).collect(),
+            domain: cfg.domain.clone(),
+            search: cfg.search.clone(),
+            sortlist: cfg.sortlist.iter().map(|n| n.to_string()).collect(),
+            options: OptionsMap {
+                debug: cfg.debug,
+                ndots: cfg.ndots,
+                timeout: cfg.timeout,
+                attempts: cfg.attempts,
+                rotate: cfg.rotate,
+                no_check_names: cfg.no_check_names,
+                inet6: cfg.inet6,
+                ip6_bytestring: cfg.ip6_bytestring,
+                ip6_dotint: cfg.ip6_dotint,
+                edns0: cfg.edns0,
+                single_request: cfg.single_request,
+                single_request_reopen: cfg.single_request_reopen,
+                no_reload: cfg.no_reload,
+                trust_ad: cfg.trust_ad,
+                no_tld_query: cfg.no_tld_query,
+                use_vc: cfg.use_vc,
+            },
+            lookup: cfg
+                .lookup
+                .iter()
+                .map(|l| match l {
+                    Lookup::File => "file".to_string(),
+                    Lookup::Bind => "bind".to_string(),
+                    Lookup::Extra(s) => s.clone(),
+                })
+                .collect(),
+            family: cfg
+                .family
+                .iter()
+                .map(|f| match f {
+                    Family::Inet4 => "inet4".to_string(),
+                    Family::Inet6 => "inet6".to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Either a structured [`ConfigMap`] or a raw `resolv.conf` document.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigRepr {
+    Structured(ConfigMap),
+    Raw(String),
+}
+
+impl Serialize for Config {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        ConfigMap::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D>(deserializer: D) -> Result<Config, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match ConfigRepr::deserialize(deserializer)? {
+            ConfigRepr::Raw(raw) => Config::parse(raw.as_bytes()).map_err(de::Error::custom),
+            ConfigRepr::Structured(map) => Config::try_from(map).map_err(de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Config {
+        let raw = "\
+domain example.com
+search example.com sub.example.com
+nameserver 8.8.8.8
+nameserver 2001:4860:4860::8888
+sortlist 130.155.160.0/255.255.240.0
+lookup file bind
+family inet4 inet6
+options ndots:3 rotate debug
+";
+        Config::parse(raw).expect("sample config should parse")
+    }
+
+    #[test]
+    fn structured_round_trips_through_json() {
+        let cfg = sample();
+        let json = serde_json::to_string(&cfg).expect("serialize");
+        let back: Config = serde_json::from_str(&json).expect("deserialize structured form");
+        assert_eq!(cfg, back);
+    }
+
+    #[test]
+    fn deserializes_from_raw_string() {
+        let cfg = sample();
+        let json = serde_json::to_string(&cfg.to_string()).expect("serialize raw string");
+        let back: Config = serde_json::from_str(&json).expect("deserialize raw form");
+        assert_eq!(cfg, back);
+    }
+
+    #[test]
+    fn rejects_invalid_raw_string() {
+        let json = serde_json::to_string("nameserver not-an-ip\n").unwrap();
+        let err = serde_json::from_str::<Config>(&json).unwrap_err();
+        assert!(err.to_string().contains("invalid IP"));
+    }
+}